@@ -0,0 +1,176 @@
+use std::path::PathBuf;
+
+use regex::Regex;
+
+use super::md_doc::{FileOffset, MdDoc};
+
+///
+/// A single fenced code block extracted from a combined markdown document
+/// - `source_path` / `start_line` point at the original nested `.md` file,
+///   not the merged buffer
+#[derive(Debug, Clone)]
+pub struct CodeTest {
+    pub source_path: PathBuf,
+    pub start_line: usize,
+    pub lang: String,
+    pub body: String,
+}
+//
+//
+impl CodeTest {
+    ///
+    /// Splits the fence's info string into its individual attributes,
+    /// e.g. `rust,no_run` or `rust should_panic` both yield `["rust", "no_run"/"should_panic"]`
+    fn attrs(&self) -> Vec<&str> {
+        self.lang.split([',', ' ']).map(str::trim).filter(|attr| !attr.is_empty()).collect()
+    }
+    ///
+    /// Returns true if this is a rust block not marked `ignore`
+    fn is_rust(&self) -> bool {
+        let attrs = self.attrs();
+        attrs.contains(&"rust") && !attrs.contains(&"ignore")
+    }
+    ///
+    /// Returns true if this block should be compiled at all
+    /// - `ignore` is the only attribute that skips compilation entirely
+    pub fn should_compile(&self) -> bool {
+        self.is_rust()
+    }
+    ///
+    /// Returns true if this block should be compiled AND executed
+    /// - `no_run` compiles but must not be executed
+    pub fn should_execute(&self) -> bool {
+        self.should_compile() && !self.attrs().contains(&"no_run")
+    }
+    ///
+    /// Returns true if executing this block is expected to panic
+    pub fn should_panic(&self) -> bool {
+        self.attrs().contains(&"should_panic")
+    }
+}
+//
+//
+impl MdDoc {
+    ///
+    /// Returns the fenced code blocks found in `self.markdown` as [CodeTest]
+    /// - `start_line` / `source_path` are resolved back through `self.offsets`
+    /// - Use [CodeTest::should_compile] / [CodeTest::should_execute] / [CodeTest::should_panic]
+    ///   to tell which blocks to compile, which to also run, and which runs are expected to panic
+    pub fn code_tests(&self) -> Vec<CodeTest> {
+        Self::extract_code_tests(&self.markdown, &self.offsets)
+    }
+    ///
+    /// Scans `markdown` line-by-line for fenced code blocks and resolves
+    /// each one's position through `offsets`
+    fn extract_code_tests(markdown: &str, offsets: &[FileOffset]) -> Vec<CodeTest> {
+        let re_fence = Regex::new(r"^\s*```(.*)").unwrap();
+        let mut tests = vec![];
+        let mut fence: Option<(usize, String, String)> = None;
+        for (line_no, line) in markdown.split('\n').enumerate() {
+            match &mut fence {
+                Some((start_line, lang, body)) => {
+                    if re_fence.is_match(line) {
+                        let (source_path, source_start_line) = Self::resolve_offset(offsets, *start_line);
+                        tests.push(CodeTest {
+                            source_path,
+                            start_line: source_start_line,
+                            lang: lang.clone(),
+                            body: body.clone(),
+                        });
+                        fence = None;
+                    } else {
+                        body.push_str(line);
+                        body.push('\n');
+                    }
+                }
+                None => {
+                    if let Some(caps) = re_fence.captures(line) {
+                        let lang = caps.get(1).map_or("", |g| g.as_str()).trim().to_owned();
+                        fence = Some((line_no, lang, String::new()));
+                    }
+                }
+            }
+        }
+        tests
+    }
+    ///
+    /// Maps `combined_line` (a line number in the combined markdown) back to the
+    /// source file that contains it, together with the corresponding line number
+    /// in that source file
+    fn resolve_offset(offsets: &[FileOffset], combined_line: usize) -> (PathBuf, usize) {
+        match offsets.iter().rev().find(|offset| offset.start_line <= combined_line) {
+            Some(offset) => (offset.path.clone(), combined_line - offset.start_line + offset.front_matter_lines),
+            None => (PathBuf::new(), combined_line),
+        }
+    }
+}
+//
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_fence_with_crlf_line_endings() {
+        let markdown = "# Title\r\n```rust\r\nfn main() {}\r\n```\r\nafter\r\n";
+        let tests = MdDoc::extract_code_tests(markdown, &[]);
+        assert_eq!(tests.len(), 1);
+        assert_eq!(tests[0].lang, "rust");
+        assert_eq!(tests[0].body, "fn main() {}\r\n");
+    }
+
+    #[test]
+    fn extracts_multiple_fences_after_a_crlf_closing_fence() {
+        let markdown = "```rust\r\nfirst\r\n```\r\n\r\n```rust\r\nsecond\r\n```\r\n";
+        let tests = MdDoc::extract_code_tests(markdown, &[]);
+        assert_eq!(tests.len(), 2);
+        assert_eq!(tests[1].body, "second\r\n");
+    }
+
+    #[test]
+    fn start_line_accounts_for_stripped_front_matter() {
+        // `a.md` = `+++\ntitle="A"\n+++\n```rust\nfn a(){}\n```\n` - its fence is at file
+        // line 3, but `offsets` only sees the combined document with front matter already
+        // stripped, so `start_line` starts at combined line 0
+        let markdown = "```rust\nfn a(){}\n```\n";
+        let offsets = vec![FileOffset { path: PathBuf::from("a.md"), start_line: 0, front_matter_lines: 3 }];
+        let tests = MdDoc::extract_code_tests(markdown, &offsets);
+        assert_eq!(tests.len(), 1);
+        assert_eq!(tests[0].source_path, PathBuf::from("a.md"));
+        assert_eq!(tests[0].start_line, 3);
+    }
+
+    fn test_with_lang(lang: &str) -> CodeTest {
+        CodeTest { source_path: PathBuf::new(), start_line: 0, lang: lang.to_owned(), body: String::new() }
+    }
+
+    #[test]
+    fn should_compile_and_execute_plain_rust() {
+        let test = test_with_lang("rust");
+        assert!(test.should_compile());
+        assert!(test.should_execute());
+        assert!(!test.should_panic());
+    }
+
+    #[test]
+    fn ignore_skips_compilation() {
+        let test = test_with_lang("rust,ignore");
+        assert!(!test.should_compile());
+        assert!(!test.should_execute());
+    }
+
+    #[test]
+    fn no_run_compiles_but_does_not_execute() {
+        let test = test_with_lang("rust no_run");
+        assert!(test.should_compile());
+        assert!(!test.should_execute());
+    }
+
+    #[test]
+    fn should_panic_compiles_executes_and_expects_a_panic() {
+        let test = test_with_lang("rust,should_panic");
+        assert!(test.should_compile());
+        assert!(test.should_execute());
+        assert!(test.should_panic());
+    }
+}
@@ -0,0 +1,109 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use serde::Deserialize;
+
+///
+/// Per-file metadata parsed from a leading `+++...+++` (TOML) or `---...---` (YAML)
+/// delimited block
+/// - Stripped from the body by [FrontMatter::strip] before the content is combined
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FrontMatter {
+    #[serde(skip)]
+    pub path: PathBuf,
+    pub title: Option<String>,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    pub date: Option<String>,
+    #[serde(alias = "order")]
+    pub weight: Option<i64>,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+//
+//
+impl FrontMatter {
+    ///
+    /// Splits `content` into its front matter (if the very first lines are a
+    /// `+++`/`---` delimited block) and the remaining body
+    /// - Returns `(None, content)` unchanged when there is no front matter,
+    ///   or when the block fails to parse
+    pub fn strip(content: &str) -> (Option<Self>, &str) {
+        let mut lines = content.split_inclusive('\n');
+        let first = lines.next().unwrap_or("");
+        let delim = match first.trim_end_matches(['\r', '\n']) {
+            "+++" => "+++",
+            "---" => "---",
+            _ => return (None, content),
+        };
+        let mut cursor = first.len();
+        for line in lines {
+            if line.trim_end_matches(['\r', '\n']) == delim {
+                let raw = &content[first.len()..cursor];
+                let body = &content[cursor + line.len()..];
+                let front_matter = if delim == "+++" { Self::from_toml(raw) } else { Self::from_yaml(raw) };
+                return match front_matter {
+                    Some(front_matter) => (Some(front_matter), body),
+                    None => (None, content),
+                };
+            }
+            cursor += line.len();
+        }
+        (None, content)
+    }
+    ///
+    /// Parses a TOML front matter block (without the `+++` fences)
+    fn from_toml(raw: &str) -> Option<Self> {
+        toml::from_str(raw).ok()
+    }
+    ///
+    /// Parses a YAML front matter block (without the `---` fences)
+    fn from_yaml(raw: &str) -> Option<Self> {
+        serde_yaml::from_str(raw).ok()
+    }
+}
+//
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_toml_front_matter() {
+        let content = "+++\ntitle = \"Intro\"\nweight = 2\ndraft = true\n+++\nbody\n";
+        let (fm, body) = FrontMatter::strip(content);
+        let fm = fm.expect("front matter should parse");
+        assert_eq!(fm.title.as_deref(), Some("Intro"));
+        assert_eq!(fm.weight, Some(2));
+        assert!(fm.draft);
+        assert_eq!(body, "body\n");
+    }
+
+    #[test]
+    fn strips_yaml_front_matter_with_order_alias() {
+        let content = "---\ntitle: Intro\norder: 3\n---\nbody\n";
+        let (fm, body) = FrontMatter::strip(content);
+        let fm = fm.expect("front matter should parse");
+        assert_eq!(fm.title.as_deref(), Some("Intro"));
+        assert_eq!(fm.weight, Some(3));
+        assert_eq!(body, "body\n");
+    }
+
+    #[test]
+    fn leaves_content_without_front_matter_untouched() {
+        let content = "# Title\nbody\n";
+        let (fm, body) = FrontMatter::strip(content);
+        assert!(fm.is_none());
+        assert_eq!(body, content);
+    }
+
+    #[test]
+    fn strips_front_matter_with_crlf_line_endings() {
+        let content = "+++\r\ntitle = \"Intro\"\r\n+++\r\nbody\r\n";
+        let (fm, body) = FrontMatter::strip(content);
+        let fm = fm.expect("front matter should parse");
+        assert_eq!(fm.title.as_deref(), Some("Intro"));
+        assert_eq!(body, "body\r\n");
+    }
+}
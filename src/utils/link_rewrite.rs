@@ -0,0 +1,128 @@
+use std::{collections::HashMap, path::{Component, Path, PathBuf}};
+
+use regex::{Captures, Regex};
+
+use super::md_doc::{FileOffset, MdDoc};
+
+//
+//
+impl MdDoc {
+    ///
+    /// Returns `self.markdown` with every relative link/image target rewritten to
+    /// resolve against `base_path` (the root `DocDir`) instead of each source file's
+    /// own directory, and intra-document `.md` links pointed at the corresponding
+    /// heading anchor instead
+    /// - Absolute URLs and anchors (`#…`) are left untouched
+    /// - Anchors are taken from [MdDoc::toc]'s `file_anchors`, so a rewritten link
+    ///   always points at the `id` that `toc()` actually injects
+    pub fn with_rewritten_links(&self, base_path: &Path) -> String {
+        let file_anchors = self.toc().file_anchors;
+        Self::rewrite_links(&self.markdown, &self.offsets, &file_anchors, base_path)
+    }
+    ///
+    /// Rewrites `markdown` line-by-line, using `offsets` to know which source file
+    /// (and therefore which directory) each line originally came from
+    fn rewrite_links(markdown: &str, offsets: &[FileOffset], file_anchors: &HashMap<PathBuf, String>, base_path: &Path) -> String {
+        let re_link = Regex::new(r#"(!?\[[^\]]*\]\()([^)\s]+)((?:\s+"[^"]*")?\))"#).unwrap();
+        let lines: Vec<&str> = markdown.split('\n').collect();
+        let last = lines.len().saturating_sub(1);
+        let mut out = String::new();
+        for (line_no, line) in lines.into_iter().enumerate() {
+            match offsets.iter().rev().find(|offset| offset.start_line <= line_no) {
+                Some(offset) => {
+                    let file_dir = Self::relative_dir(&offset.path, base_path);
+                    let rewritten = re_link.replace_all(line, |caps: &Captures| {
+                        let target = Self::rewrite_target(&caps[2], &file_dir, file_anchors, base_path);
+                        format!("{}{}{}", &caps[1], target, &caps[3])
+                    });
+                    out.push_str(&rewritten);
+                }
+                None => out.push_str(line),
+            }
+            if line_no != last {
+                out.push('\n');
+            }
+        }
+        out
+    }
+    ///
+    /// Rewrites a single link/image `target` found in a file living at `file_dir`
+    /// (relative to `base_path`)
+    fn rewrite_target(target: &str, file_dir: &Path, file_anchors: &HashMap<PathBuf, String>, base_path: &Path) -> String {
+        if target.is_empty() || target.starts_with('#') || target.contains("://") {
+            return target.to_owned();
+        }
+        let (path_part, fragment) = match target.split_once('#') {
+            Some((path_part, fragment)) => (path_part, Some(fragment)),
+            None => (target, None),
+        };
+        let resolved = Self::normalize_path(&file_dir.join(path_part));
+        if path_part.ends_with(".md") {
+            format!("#{}", Self::anchor_for(&resolved, base_path, file_anchors))
+        } else {
+            match fragment {
+                Some(fragment) => format!("{}#{}", resolved.display(), fragment),
+                None => resolved.display().to_string(),
+            }
+        }
+    }
+    ///
+    /// Returns the heading anchor that an intra-document `.md` link should now point at,
+    /// taken from the real slug `toc()` assigned to the target file's own first heading
+    fn anchor_for(resolved: &Path, base_path: &Path, file_anchors: &HashMap<PathBuf, String>) -> String {
+        file_anchors.iter()
+            .find(|(path, _)| path.strip_prefix(base_path).unwrap_or(path) == resolved)
+            .map_or_else(String::new, |(_, slug)| slug.clone())
+    }
+    ///
+    /// Returns `path`'s parent directory, relative to `base_path`
+    fn relative_dir(path: &Path, base_path: &Path) -> PathBuf {
+        let relative = path.strip_prefix(base_path).unwrap_or(path);
+        relative.parent().map(Path::to_path_buf).unwrap_or_default()
+    }
+    ///
+    /// Collapses `.` and `..` path components without touching the filesystem
+    fn normalize_path(path: &Path) -> PathBuf {
+        let mut normalized = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::ParentDir => { normalized.pop(); }
+                Component::CurDir => {}
+                component => normalized.push(component),
+            }
+        }
+        normalized
+    }
+}
+//
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_intra_doc_link_to_the_tocs_real_slug() {
+        let markdown = "[see other](../other.md)\n";
+        let offsets = vec![FileOffset { path: PathBuf::from("/docs/ch1/page.md"), start_line: 0, front_matter_lines: 0 }];
+        let mut file_anchors = HashMap::new();
+        file_anchors.insert(PathBuf::from("other.md"), "part-02-other".to_owned());
+        let rewritten = MdDoc::rewrite_links(markdown, &offsets, &file_anchors, Path::new("/docs/ch1"));
+        assert_eq!(rewritten, "[see other](#part-02-other)\n");
+    }
+
+    #[test]
+    fn rewrites_relative_image_against_the_base_path() {
+        let markdown = "![x](../img/x.png)\n";
+        let offsets = vec![FileOffset { path: PathBuf::from("/docs/ch1/page.md"), start_line: 0, front_matter_lines: 0 }];
+        let rewritten = MdDoc::rewrite_links(markdown, &offsets, &HashMap::new(), Path::new("/docs"));
+        assert_eq!(rewritten, "![x](img/x.png)\n");
+    }
+
+    #[test]
+    fn leaves_absolute_urls_and_anchors_untouched() {
+        let markdown = "[ext](https://example.com) [anchor](#section)\n";
+        let offsets = vec![FileOffset { path: PathBuf::from("/docs/page.md"), start_line: 0, front_matter_lines: 0 }];
+        let rewritten = MdDoc::rewrite_links(markdown, &offsets, &HashMap::new(), Path::new("/docs"));
+        assert_eq!(rewritten, markdown);
+    }
+}
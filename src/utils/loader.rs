@@ -0,0 +1,135 @@
+use std::{collections::HashMap, fs, process::Command};
+
+///
+/// Resolves a leaf document's content by extension (or `http(s)://` scheme),
+/// shelling out to an external converter for non-markdown sources
+/// - Data-driven: `$1` in each template is substituted with the path/URL
+/// - Falls back to reading `.md` files directly; unknown extensions are logged and skipped
+pub struct LoaderRegistry {
+    loaders: HashMap<String, String>,
+}
+//
+//
+impl LoaderRegistry {
+    ///
+    /// Returns [LoaderRegistry] new instance with the default `pdf` / `docx` / `url` loaders
+    pub fn new() -> Self {
+        Self {
+            loaders: HashMap::from([
+                ("pdf".to_owned(), "pdftotext $1 -".to_owned()),
+                ("docx".to_owned(), "pandoc --to markdown $1".to_owned()),
+                ("url".to_owned(), "curl -fsSL $1".to_owned()),
+            ]),
+        }
+    }
+    ///
+    /// Registers (or overrides) the loader command template for `key`
+    /// (a file extension without the dot, or `url`)
+    pub fn register(&mut self, key: impl Into<String>, command: impl Into<String>) {
+        self.loaders.insert(key.into(), command.into());
+    }
+    ///
+    /// Loads `source` (a file path or `http(s)://` URL) into a markdown/plaintext string
+    /// - `.md` files are read directly, bypassing the loader registry
+    /// - Unknown extensions log a warning and return `None`
+    pub fn load(&self, source: &str) -> Option<String> {
+        if source.starts_with("http://") || source.starts_with("https://") {
+            return self.run(self.loaders.get("url")?, source);
+        }
+        match source.rsplit('.').next() {
+            Some("md") => fs::read_to_string(source).ok(),
+            Some(ext) => match self.loaders.get(ext) {
+                Some(template) => self.run(template, source),
+                None => {
+                    log::warn!("LoaderRegistry.load | No loader registered for extension: '{}'", ext);
+                    None
+                }
+            },
+            None => None,
+        }
+    }
+    ///
+    /// Runs `template` (a whitespace-separated `program arg arg…` command line, with `$1`
+    /// standing in for `source`) and captures stdout
+    /// - Executed directly via [Command], with `source` passed as a single argument -
+    ///   no shell is invoked, so shell metacharacters in `source` can't be interpreted
+    fn run(&self, template: &str, source: &str) -> Option<String> {
+        let mut tokens = template.split_whitespace();
+        let program = tokens.next()?;
+        let args: Vec<String> = tokens.map(|arg| if arg == "$1" { source.to_owned() } else { arg.to_owned() }).collect();
+        log::debug!("LoaderRegistry.run | program: '{}', args: {:?}", program, args);
+        match Command::new(program).args(&args).output() {
+            Ok(output) if output.status.success() => String::from_utf8(output.stdout).ok(),
+            Ok(output) => {
+                log::warn!("LoaderRegistry.run | Command failed: '{} {:?}': \n\t{}", program, args, String::from_utf8_lossy(&output.stderr));
+                None
+            }
+            Err(err) => {
+                log::warn!("LoaderRegistry.run | Error running: '{} {:?}': \n\t{:#?}", program, args, err);
+                None
+            }
+        }
+    }
+}
+//
+//
+impl Default for LoaderRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+//
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_substitutes_dollar_one_with_the_source_as_a_single_argument() {
+        let registry = LoaderRegistry::new();
+        let content = registry.run("echo $1", "hello world").expect("echo should succeed");
+        assert_eq!(content, "hello world\n");
+    }
+
+    #[test]
+    fn run_returns_none_when_the_command_fails() {
+        let registry = LoaderRegistry::new();
+        assert_eq!(registry.run("false", "x"), None);
+    }
+
+    #[test]
+    fn run_returns_none_when_the_program_is_missing() {
+        let registry = LoaderRegistry::new();
+        assert_eq!(registry.run("", "x"), None);
+    }
+
+    #[test]
+    fn load_reads_markdown_files_directly() {
+        let path = std::env::temp_dir().join(format!("loader_test_{}.md", std::process::id()));
+        fs::write(&path, "# Title\n").unwrap();
+        let registry = LoaderRegistry::new();
+        let content = registry.load(&path.to_string_lossy());
+        fs::remove_file(&path).unwrap();
+        assert_eq!(content, Some("# Title\n".to_owned()));
+    }
+
+    #[test]
+    fn load_routes_a_registered_extension_through_its_loader() {
+        let mut registry = LoaderRegistry::new();
+        registry.register("txt", "echo $1");
+        assert_eq!(registry.load("sample.txt"), Some("sample.txt\n".to_owned()));
+    }
+
+    #[test]
+    fn load_returns_none_for_an_unregistered_extension() {
+        let registry = LoaderRegistry::new();
+        assert_eq!(registry.load("sample.xyz"), None);
+    }
+
+    #[test]
+    fn load_routes_http_urls_through_the_url_loader() {
+        let mut registry = LoaderRegistry::new();
+        registry.register("url", "echo $1");
+        assert_eq!(registry.load("https://example.com/page"), Some("https://example.com/page\n".to_owned()));
+    }
+}
@@ -1,9 +1,23 @@
 use std::fs;
+use std::path::PathBuf;
 
 use regex::Regex;
 
-use super::{doc_dir::DocDir, eval::Eval, title_page::Title};
+use super::{doc_dir::DocDir, eval::Eval, front_matter::FrontMatter, loader::LoaderRegistry, title_page::Title};
 
+///
+/// Marks the line (in the combined `markdown`) where a single source file's
+/// content begins, so positions in the merged document can be mapped back
+/// to the original nested `.md` file
+#[derive(Debug, Clone)]
+pub struct FileOffset {
+    pub path: PathBuf,
+    pub start_line: usize,
+    /// Number of lines stripped off the front of the source file (its front matter
+    /// block, if any) before `start_line` was recorded - added back when mapping a
+    /// combined-document line back to the corresponding line in the source file
+    pub front_matter_lines: usize,
+}
 ///
 /// Marcdown document
 /// - Reads from file of folder
@@ -14,6 +28,8 @@ pub struct MdDoc {
     pub title: Option<Title>,
     pub markdown: String,
     pub html: String,
+    pub offsets: Vec<FileOffset>,
+    pub front_matter: Vec<FrontMatter>,
 }
 //
 //
@@ -33,35 +49,98 @@ impl MdDoc {
             title: None,
             markdown: String::new(),
             html: String::new(),
+            offsets: vec![],
+            front_matter: vec![],
         }
     }
     ///
     /// Returns [MdDoc] new instance with specified `html_body`
     pub fn with_html(self, html: String,) -> Self {
-        Self { dir: self.dir, title: self.title, markdown: self.markdown, html }
+        Self { dir: self.dir, title: self.title, markdown: self.markdown, html, offsets: self.offsets, front_matter: self.front_matter }
     }
     ///
     /// Returns [MdDoc] new instance with specified `markdown`
     pub fn with_md(self, md: String,) -> Self {
-        Self { dir: self.dir, title: self.title, markdown: md, html: self.html }
+        Self { dir: self.dir, title: self.title, markdown: md, html: self.html, offsets: self.offsets, front_matter: self.front_matter }
     }
     ///
     /// Returns joined `title` and `body`
     pub fn joined(&self) -> String {
         format!("{}{}", self.title.clone().map_or("".into(), |t| t.raw), self.markdown)
     }
-    /// 
+    ///
+    /// Renders `markdown` into `html`, highlighting fenced code blocks
+    /// - Each ` ```{lang} ` … ` ``` ` fence becomes
+    ///   `<div class="example-wrap"><pre class="language-{lang}"><code>{escaped}</code></pre></div>`
+    /// - The first whitespace-separated word of the fence's info string (e.g. `rust`,
+    ///   or `ignore` for `ignore no_run`) is escaped the same way as `body` before being
+    ///   used for `{lang}`, empty when the fence has no info string
+    /// - Indented code blocks and all other markdown are left untouched
+    fn render_html(markdown: &str) -> String {
+        let re_fence = Regex::new(r"^\s*```(\S*)").unwrap();
+        let mut html = String::new();
+        let mut fence: Option<(String, String)> = None;
+        for line in markdown.split('\n') {
+            match &mut fence {
+                Some((lang, body)) => {
+                    if re_fence.is_match(line) {
+                        html.push_str(&format!(
+                            "<div class=\"example-wrap\"><pre class=\"language-{}\"><code>{}</code></pre></div>\n",
+                            Self::escape_html(lang),
+                            Self::escape_html(body),
+                        ));
+                        fence = None;
+                    } else {
+                        body.push_str(line);
+                        body.push('\n');
+                    }
+                }
+                None => match re_fence.captures(line) {
+                    Some(caps) => {
+                        let lang = caps.get(1).map_or("", |g| g.as_str()).to_owned();
+                        fence = Some((lang, String::new()));
+                    }
+                    None => {
+                        html.push_str(line);
+                        html.push('\n');
+                    }
+                },
+            }
+        }
+        html
+    }
+    ///
+    /// Escapes `<`, `>`, `&` and `"` so raw text can be safely embedded in html
+    pub(crate) fn escape_html(text: &str) -> String {
+        text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+    ///
     /// Add page brakes
-    fn add_pagebreakes(doc: &str) -> String {
+    /// - Also shifts `offsets` so each `start_line` keeps pointing at the same
+    ///   source line after pagebreaks are inserted
+    fn add_pagebreakes(doc: &str, offsets: &mut [FileOffset]) -> String {
         let lines: Vec<&str> = doc.split("\n").collect();
         let mut doc = String::new();
+        let original_lines: Vec<usize> = offsets.iter().map(|offset| offset.start_line).collect();
+        let shift = |offsets: &mut [FileOffset], old_line: usize, new_line: usize| {
+            for (offset, original_line) in offsets.iter_mut().zip(&original_lines) {
+                if *original_line == old_line {
+                    offset.start_line = new_line;
+                }
+            }
+        };
         if let Some(line) = lines.first() {
+            shift(offsets, 0, 0);
             doc.push_str(line);
             doc.push_str("\n");
         }
         let mut prev_is_empty = false;
         let re_is_empty = Regex::new(r#"(^\s*$)"#).unwrap();
-        for line in lines.into_iter().skip(1) {
+        for (old_line, line) in lines.into_iter().enumerate().skip(1) {
             if line.starts_with("# ") {
                 if !prev_is_empty {
                     doc.push_str("\n\n");
@@ -69,6 +148,7 @@ impl MdDoc {
                 doc.push_str(MdDoc::PAGEBREAK);
                 doc.push_str("\n\n");
             }
+            shift(offsets, old_line, doc.matches('\n').count());
             doc.push_str(line);
             doc.push_str("\n");
             prev_is_empty = re_is_empty.is_match(line);
@@ -78,7 +158,11 @@ impl MdDoc {
     ///
     /// Returns marckdown document
     /// combined from multiple md files stored in the nested folders
-    fn combine(dir: &DocDir, body: &mut String, title: &mut Option<Title>) {
+    /// - Records, in `offsets`, the line in `body` where each source file's content begins
+    /// - Records, in `front_matter`, each leaf file's parsed front matter (if any)
+    /// - Sorts sibling leaf files by their front matter `weight`/`order` and skips `draft` ones
+    /// - Non-`.md` sources (and `http(s)://` links in `DocDir`) are converted via `loaders`
+    fn combine(dir: &DocDir, body: &mut String, title: &mut Option<Title>, offsets: &mut Vec<FileOffset>, front_matter: &mut Vec<FrontMatter>, loaders: &LoaderRegistry) {
         log::debug!("Doc.combine | path: '{:?}'", dir.path);
         if !dir.is_dir {
             println!("\t{:?}", dir.path);
@@ -92,24 +176,35 @@ impl MdDoc {
                     None => {} //log::warn!("Doc.combine | Title page is not fount in: {}", dir.path.display()),
                 };
             }
-            match fs::read_to_string(&dir.path) {
-                Ok(content) => {
+            match loaders.load(&dir.path.to_string_lossy()) {
+                Some(content) => {
                     log::trace!("Doc.combine | Content: {:#?}", content);
-                    body.push_str(&content);
+                    let (fm, stripped) = FrontMatter::strip(&content);
+                    let front_matter_lines = content[..content.len() - stripped.len()].matches('\n').count();
+                    if let Some(mut fm) = fm {
+                        fm.path = dir.path.clone();
+                        front_matter.push(fm);
+                    }
+                    offsets.push(FileOffset { path: dir.path.clone(), start_line: body.matches('\n').count(), front_matter_lines });
+                    body.push_str(stripped);
                 }
-                Err(err) => log::debug!("Doc.combine | Error read filr: {}: \n\t{:#?}", dir.path.display(), err),
+                None => log::debug!("Doc.combine | Error loading file: {}", dir.path.display()),
             }
         } else {
             body.push_str(&Self::read_header(&dir));
-            let children = dir.children.iter().filter(|child| {
+            let mut children: Vec<&DocDir> = dir.children.iter().filter(|child| {
                 if child.is_dir {
                     true
                 } else {
                     child.header() != dir.header()
                 }
-            });
+            }).collect();
+            children.sort_by_key(|child| Self::front_matter_of(child).and_then(|fm| fm.weight).unwrap_or(0));
             for child in children {
-                Self::combine(child, body, title)
+                if Self::front_matter_of(child).is_some_and(|fm| fm.draft) {
+                    continue;
+                }
+                Self::combine(child, body, title, offsets, front_matter, loaders)
             }
             if !body.ends_with("\n\n") {
                 body.push_str("\n\n");
@@ -121,6 +216,16 @@ impl MdDoc {
         }
     }
     ///
+    /// Returns the front matter of a leaf file, without consuming its content
+    /// - Returns `None` for directories or files without a front matter block
+    fn front_matter_of(dir: &DocDir) -> Option<FrontMatter> {
+        if dir.is_dir {
+            return None;
+        }
+        let content = fs::read_to_string(&dir.path).ok()?;
+        FrontMatter::strip(&content).0
+    }
+    ///
     /// Returns true if string has page break at the end
     fn ends_with_pagebreak(doc: &str) -> bool {
         let re_non_whitespace = Regex::new(r"\S").unwrap();
@@ -190,13 +295,33 @@ impl Eval<(), Self> for MdDoc {
         log::debug!("Doc.eval | path: '{:?}'", self.dir.path);
         let mut body = String::new();
         let mut title = None;
-        Self::combine(&self.dir, &mut body, &mut title);
-        let body = Self::add_pagebreakes(&body);
+        let mut offsets = vec![];
+        let mut front_matter = vec![];
+        let loaders = LoaderRegistry::new();
+        Self::combine(&self.dir, &mut body, &mut title, &mut offsets, &mut front_matter, &loaders);
+        let body = Self::add_pagebreakes(&body, &mut offsets);
+        let html = Self::render_html(&body);
         Self {
             dir: self.dir.clone(),
             title,
             markdown: body,
-            html: String::new(),
+            html,
+            offsets,
+            front_matter,
         }
     }
 }
+//
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_the_fences_info_string_in_rendered_html() {
+        let markdown = "```\"><script>alert(1)</script>\nfn a() {}\n```\n";
+        let html = MdDoc::render_html(markdown);
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("language-&quot;&gt;&lt;script&gt;alert(1)&lt;/script&gt;"));
+    }
+}
@@ -0,0 +1,9 @@
+pub mod doc_dir;
+pub mod eval;
+pub mod title_page;
+pub mod md_doc;
+pub mod code_test;
+pub mod front_matter;
+pub mod loader;
+pub mod toc;
+pub mod link_rewrite;
@@ -0,0 +1,186 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use regex::Regex;
+
+use super::md_doc::{FileOffset, MdDoc};
+
+///
+/// A single heading in the table of contents, nested under the nearest
+/// preceding heading of a lower level
+#[derive(Debug, Clone)]
+pub struct TocEntry {
+    pub level: u8,
+    pub text: String,
+    pub slug: String,
+    pub page: Option<usize>,
+    pub children: Vec<TocEntry>,
+}
+///
+/// Table of contents built over a combined markdown document
+pub struct Toc {
+    pub entries: Vec<TocEntry>,
+    pub nav_html: String,
+    pub body: String,
+    /// Maps each source file to the slug of its own first heading, so other
+    /// passes (e.g. intra-document link rewriting) can resolve whole-file anchors
+    /// without recomputing their own slug
+    pub file_anchors: HashMap<PathBuf, String>,
+}
+//
+//
+impl MdDoc {
+    ///
+    /// Builds a [Toc] from `self.markdown`:
+    /// - `entries` - the nested heading outline
+    /// - `nav_html` - the outline rendered as `<nav><ul>…</ul></nav>`
+    /// - `body` - `self.markdown` with `id="{slug}"` anchors injected into every heading
+    /// - `file_anchors` - each source file's own first heading slug
+    pub fn toc(&self) -> Toc {
+        Self::build_toc(&self.markdown, &self.offsets)
+    }
+    ///
+    /// Walks `markdown` line-by-line, collecting headings into a nested outline
+    /// while injecting `id="{slug}"` anchors and tracking the page each one starts on
+    /// - Fenced code blocks are skipped entirely: a `#` inside one is a comment, not a heading
+    fn build_toc(markdown: &str, offsets: &[FileOffset]) -> Toc {
+        let re_heading = Regex::new(r"^(#{1,6})\s+(.*?)\s*$").unwrap();
+        let re_fence = Regex::new(r"^\s*```").unwrap();
+        let mut roots: Vec<TocEntry> = vec![];
+        let mut open: Vec<TocEntry> = vec![];
+        let mut slug_counts: HashMap<String, usize> = HashMap::new();
+        let mut file_anchors: HashMap<PathBuf, String> = HashMap::new();
+        let mut body = String::new();
+        let mut page = 0usize;
+        let mut in_fence = false;
+        for (line_no, line) in markdown.split('\n').enumerate() {
+            if re_fence.is_match(line) {
+                in_fence = !in_fence;
+                body.push_str(line);
+                body.push('\n');
+                continue;
+            }
+            if in_fence {
+                body.push_str(line);
+                body.push('\n');
+                continue;
+            }
+            if line.contains(Self::PAGEBREAK) {
+                page += 1;
+                body.push_str(line);
+                body.push('\n');
+                continue;
+            }
+            match re_heading.captures(line) {
+                Some(caps) => {
+                    let level = caps[1].len() as u8;
+                    let text = caps[2].to_owned();
+                    let slug = Self::unique_slug(&Self::slugify(&text), &mut slug_counts);
+                    Self::close_headings(&mut open, &mut roots, level);
+                    open.push(TocEntry { level, text: text.clone(), slug: slug.clone(), page: Some(page), children: vec![] });
+                    if let Some(offset) = offsets.iter().rev().find(|offset| offset.start_line <= line_no) {
+                        file_anchors.entry(offset.path.clone()).or_insert_with(|| slug.clone());
+                    }
+                    body.push_str(&format!("{} <span id=\"{}\">{}</span>", &caps[1], slug, Self::escape_html(&text)));
+                    body.push('\n');
+                }
+                None => {
+                    body.push_str(line);
+                    body.push('\n');
+                }
+            }
+        }
+        Self::close_headings(&mut open, &mut roots, 0);
+        let nav_html = Self::render_nav(&roots);
+        Toc { entries: roots, nav_html, body, file_anchors }
+    }
+    ///
+    /// Pops every open heading at `level` or deeper off `open`, attaching each to its
+    /// parent (the next-shallower open heading) or to `roots` when none remains
+    fn close_headings(open: &mut Vec<TocEntry>, roots: &mut Vec<TocEntry>, level: u8) {
+        while open.last().is_some_and(|entry| entry.level >= level) {
+            let entry = open.pop().unwrap();
+            match open.last_mut() {
+                Some(parent) => parent.children.push(entry),
+                None => roots.push(entry),
+            }
+        }
+    }
+    ///
+    /// Derives a URL-safe slug: lowercase, spaces/punctuation collapsed to `-`
+    pub(crate) fn slugify(text: &str) -> String {
+        let mut slug = String::new();
+        let mut prev_dash = false;
+        for c in text.to_lowercase().chars() {
+            if c.is_alphanumeric() {
+                slug.push(c);
+                prev_dash = false;
+            } else if !prev_dash && !slug.is_empty() {
+                slug.push('-');
+                prev_dash = true;
+            }
+        }
+        slug.trim_end_matches('-').to_owned()
+    }
+    ///
+    /// De-duplicates `slug` against previously seen slugs by appending `-1`, `-2`, …
+    fn unique_slug(slug: &str, counts: &mut HashMap<String, usize>) -> String {
+        let count = counts.entry(slug.to_owned()).or_insert(0);
+        let unique = if *count == 0 { slug.to_owned() } else { format!("{}-{}", slug, count) };
+        *count += 1;
+        unique
+    }
+    ///
+    /// Renders a nested `<nav><ul>…</ul></nav>` outline from `entries`
+    fn render_nav(entries: &[TocEntry]) -> String {
+        format!("<nav>\n{}</nav>\n", Self::render_nav_list(entries))
+    }
+    ///
+    /// Renders `entries` as a (possibly nested) `<ul>` list
+    fn render_nav_list(entries: &[TocEntry]) -> String {
+        let mut html = String::from("<ul>\n");
+        for entry in entries {
+            html.push_str(&format!("<li><a href=\"#{}\">{}</a>", entry.slug, Self::escape_html(&entry.text)));
+            if !entry.children.is_empty() {
+                html.push('\n');
+                html.push_str(&Self::render_nav_list(&entry.children));
+            }
+            html.push_str("</li>\n");
+        }
+        html.push_str("</ul>\n");
+        html
+    }
+}
+//
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_headings_inside_fenced_code_blocks() {
+        let markdown = "# Real Heading\n```bash\n# comment, not a heading\necho hi\n```\n## Another Real Heading\n";
+        let toc = MdDoc::build_toc(markdown, &[]);
+        assert_eq!(toc.entries.len(), 1);
+        assert_eq!(toc.entries[0].text, "Real Heading");
+        assert_eq!(toc.entries[0].children.len(), 1);
+        assert_eq!(toc.entries[0].children[0].text, "Another Real Heading");
+        assert!(toc.body.contains("# comment, not a heading"));
+        assert!(!toc.body.contains("<span id=\"comment-not-a-heading\">"));
+    }
+
+    #[test]
+    fn escapes_heading_text_injected_into_the_body_span() {
+        let markdown = "# A <script>alert(1)</script> & \"B\"\n";
+        let toc = MdDoc::build_toc(markdown, &[]);
+        assert!(!toc.body.contains("<script>alert(1)</script>"));
+        assert!(toc.body.contains("&lt;script&gt;alert(1)&lt;/script&gt; &amp; &quot;B&quot;"));
+    }
+
+    #[test]
+    fn builds_file_anchors_from_each_files_first_heading() {
+        let markdown = "# Part 01. Intro\ntext\n## Details\n";
+        let offsets = vec![FileOffset { path: PathBuf::from("intro.md"), start_line: 0, front_matter_lines: 0 }];
+        let toc = MdDoc::build_toc(markdown, &offsets);
+        assert_eq!(toc.file_anchors.get(&PathBuf::from("intro.md")), Some(&"part-01-intro".to_owned()));
+    }
+}